@@ -0,0 +1,424 @@
+//! Pure-Rust gzip/tar decoding so a published `.crate` file (a gzip-compressed
+//! tar archive) can be mounted without any JS-side unpacking.
+
+use wasm_bindgen::JsValue;
+
+use crate::InMemoryVfs;
+
+/// Internal error type for the decoder. Kept as a plain string rather than
+/// `JsValue` so this module's logic (and its tests) can run on any target;
+/// only the public [`mount_crate_archive`] boundary converts to `JsValue`.
+type ArchiveResult<T> = Result<T, String>;
+
+/// Reads DEFLATE-compressed bits LSB-first, as required by RFC 1951.
+struct BitReader<'a> {
+    data: &'a [u8],
+    byte_pos: usize,
+    bit_pos: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            byte_pos: 0,
+            bit_pos: 0,
+        }
+    }
+
+    fn read_bit(&mut self) -> ArchiveResult<u32> {
+        let byte = *self
+            .data
+            .get(self.byte_pos)
+            .ok_or_else(|| "inflate: unexpected end of stream".to_string())?;
+        let bit = (byte >> self.bit_pos) & 1;
+        self.bit_pos += 1;
+        if self.bit_pos == 8 {
+            self.bit_pos = 0;
+            self.byte_pos += 1;
+        }
+        Ok(bit as u32)
+    }
+
+    fn read_bits(&mut self, count: u32) -> ArchiveResult<u32> {
+        let mut value = 0u32;
+        for i in 0..count {
+            value |= self.read_bit()? << i;
+        }
+        Ok(value)
+    }
+
+    fn align_to_byte(&mut self) {
+        if self.bit_pos != 0 {
+            self.bit_pos = 0;
+            self.byte_pos += 1;
+        }
+    }
+
+    fn read_bytes(&mut self, count: usize) -> ArchiveResult<&'a [u8]> {
+        let start = self.byte_pos;
+        let end = start + count;
+        let slice = self
+            .data
+            .get(start..end)
+            .ok_or_else(|| "inflate: unexpected end of stream".to_string())?;
+        self.byte_pos = end;
+        Ok(slice)
+    }
+}
+
+/// Canonical Huffman decoder built from a list of per-symbol code lengths,
+/// following the counts/symbols construction from RFC 1951 section 3.2.2.
+struct HuffmanTree {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl HuffmanTree {
+    fn build(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for len in 1..16 {
+            offsets[len] = offsets[len - 1] + counts[len - 1];
+        }
+
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> ArchiveResult<u16> {
+        let mut code = 0i32;
+        let mut first = 0i32;
+        let mut index = 0i32;
+        for len in 1..16 {
+            code |= reader.read_bit()? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err("inflate: invalid Huffman code".to_string())
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn fixed_trees() -> (HuffmanTree, HuffmanTree) {
+    let mut litlen_lengths = [0u8; 288];
+    for (i, len) in litlen_lengths.iter_mut().enumerate() {
+        *len = match i {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    let dist_lengths = [5u8; 30];
+    (
+        HuffmanTree::build(&litlen_lengths),
+        HuffmanTree::build(&dist_lengths),
+    )
+}
+
+fn dynamic_trees(reader: &mut BitReader) -> ArchiveResult<(HuffmanTree, HuffmanTree)> {
+    let hlit = reader.read_bits(5)? as usize + 257;
+    let hdist = reader.read_bits(5)? as usize + 1;
+    let hclen = reader.read_bits(4)? as usize + 4;
+
+    let mut code_length_lengths = [0u8; 19];
+    for &order in CODE_LENGTH_ORDER.iter().take(hclen) {
+        code_length_lengths[order] = reader.read_bits(3)? as u8;
+    }
+    let code_length_tree = HuffmanTree::build(&code_length_lengths);
+
+    let mut lengths = Vec::with_capacity(hlit + hdist);
+    while lengths.len() < hlit + hdist {
+        let symbol = code_length_tree.decode(reader)?;
+        match symbol {
+            0..=15 => lengths.push(symbol as u8),
+            16 => {
+                let prev = *lengths
+                    .last()
+                    .ok_or_else(|| "inflate: repeat with no previous length".to_string())?;
+                let repeat = reader.read_bits(2)? + 3;
+                for _ in 0..repeat {
+                    lengths.push(prev);
+                }
+            }
+            17 => {
+                let repeat = reader.read_bits(3)? + 3;
+                lengths.resize(lengths.len() + repeat as usize, 0);
+            }
+            18 => {
+                let repeat = reader.read_bits(7)? + 11;
+                lengths.resize(lengths.len() + repeat as usize, 0);
+            }
+            _ => return Err("inflate: invalid code length symbol".to_string()),
+        }
+    }
+
+    let litlen_tree = HuffmanTree::build(&lengths[..hlit]);
+    let dist_tree = HuffmanTree::build(&lengths[hlit..]);
+    Ok((litlen_tree, dist_tree))
+}
+
+/// Inflates a raw DEFLATE stream (RFC 1951), as found inside a gzip member.
+fn inflate(data: &[u8]) -> ArchiveResult<Vec<u8>> {
+    let mut reader = BitReader::new(data);
+    let mut out = Vec::new();
+
+    loop {
+        let is_final = reader.read_bit()? == 1;
+        let block_type = reader.read_bits(2)?;
+
+        match block_type {
+            0 => {
+                reader.align_to_byte();
+                let len_bytes = reader.read_bytes(4)?;
+                let len = u16::from_le_bytes([len_bytes[0], len_bytes[1]]) as usize;
+                out.extend_from_slice(reader.read_bytes(len)?);
+            }
+            1 | 2 => {
+                let (litlen_tree, dist_tree) = if block_type == 1 {
+                    fixed_trees()
+                } else {
+                    dynamic_trees(&mut reader)?
+                };
+                loop {
+                    let symbol = litlen_tree.decode(&mut reader)?;
+                    if symbol < 256 {
+                        out.push(symbol as u8);
+                    } else if symbol == 256 {
+                        break;
+                    } else {
+                        let idx = (symbol - 257) as usize;
+                        let length = LENGTH_BASE[idx] as usize
+                            + reader.read_bits(LENGTH_EXTRA[idx] as u32)? as usize;
+                        let dist_symbol = dist_tree.decode(&mut reader)? as usize;
+                        let distance = DIST_BASE[dist_symbol] as usize
+                            + reader.read_bits(DIST_EXTRA[dist_symbol] as u32)? as usize;
+                        if distance > out.len() {
+                            return Err("inflate: distance too far back".to_string());
+                        }
+                        let start = out.len() - distance;
+                        for i in 0..length {
+                            let byte = out[start + i];
+                            out.push(byte);
+                        }
+                    }
+                }
+            }
+            _ => return Err("inflate: reserved block type".to_string()),
+        }
+
+        if is_final {
+            break;
+        }
+    }
+
+    Ok(out)
+}
+
+const FTEXT: u8 = 1 << 0;
+const FHCRC: u8 = 1 << 1;
+const FEXTRA: u8 = 1 << 2;
+const FNAME: u8 = 1 << 3;
+const FCOMMENT: u8 = 1 << 4;
+
+/// Strips the gzip header/trailer and inflates the DEFLATE payload.
+fn gunzip(bytes: &[u8]) -> ArchiveResult<Vec<u8>> {
+    if bytes.len() < 10 || bytes[0] != 0x1f || bytes[1] != 0x8b {
+        return Err("not a gzip stream".to_string());
+    }
+    if bytes[2] != 0x08 {
+        return Err("unsupported gzip compression method".to_string());
+    }
+    let flags = bytes[3];
+    let _ = FTEXT;
+    let mut pos = 10usize;
+
+    if flags & FEXTRA != 0 {
+        let xlen = u16::from_le_bytes([
+            *bytes.get(pos).ok_or_else(truncated)?,
+            *bytes.get(pos + 1).ok_or_else(truncated)?,
+        ]) as usize;
+        pos += 2 + xlen;
+    }
+    if flags & FNAME != 0 {
+        while *bytes.get(pos).ok_or_else(truncated)? != 0 {
+            pos += 1;
+        }
+        pos += 1;
+    }
+    if flags & FCOMMENT != 0 {
+        while *bytes.get(pos).ok_or_else(truncated)? != 0 {
+            pos += 1;
+        }
+        pos += 1;
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+
+    let payload = bytes.get(pos..).ok_or_else(truncated)?;
+    inflate(payload)
+}
+
+fn truncated() -> String {
+    "gzip: unexpected end of header".to_string()
+}
+
+/// Walks a (decompressed) tar byte stream, yielding `(path, data)` for every
+/// regular file, with the leading `name-version/` directory component
+/// stripped so paths line up with the directory picker.
+fn tar_entries(tar: &[u8]) -> Vec<(String, Vec<u8>)> {
+    const BLOCK: usize = 512;
+    let mut entries = Vec::new();
+    let mut pos = 0usize;
+
+    while pos + BLOCK <= tar.len() {
+        let header = &tar[pos..pos + BLOCK];
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let name = ascii_field(&header[0..100]);
+        let size_field = ascii_field(&header[124..136]);
+        let size = usize::from_str_radix(size_field.trim(), 8).unwrap_or(0);
+        let typeflag = header[156];
+
+        pos += BLOCK;
+        let data_start = pos;
+        let padded = size.div_ceil(BLOCK) * BLOCK;
+        pos += padded;
+
+        if (typeflag == b'0' || typeflag == 0) && !name.is_empty() {
+            let data = tar
+                .get(data_start..data_start + size)
+                .unwrap_or_default()
+                .to_vec();
+            let stripped = match name.split_once('/') {
+                Some((_, rest)) if !rest.is_empty() => rest.to_string(),
+                _ => name,
+            };
+            entries.push((stripped, data));
+        }
+    }
+
+    entries
+}
+
+fn ascii_field(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).trim().to_string()
+}
+
+/// Imports a published `.crate` file (or any gzip-compressed tar archive)
+/// directly into an [`InMemoryVfs`], without any directory picker.
+pub fn mount_crate_archive(bytes: Vec<u8>) -> Result<InMemoryVfs, JsValue> {
+    let tar = gunzip(&bytes).map_err(|e| JsValue::from_str(&e))?;
+    let mut files = std::collections::BTreeMap::new();
+    for (path, data) in tar_entries(&tar) {
+        files.insert(path, data);
+    }
+    Ok(InMemoryVfs { files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vfs;
+
+    fn tar_header(name: &str, size: usize) -> [u8; 512] {
+        let mut header = [0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        let size_field = format!("{size:011o}\0");
+        header[124..124 + size_field.len()].copy_from_slice(size_field.as_bytes());
+        header[156] = b'0'; // typeflag: regular file
+        header
+    }
+
+    fn tar_entry(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut entry = tar_header(name, data.len()).to_vec();
+        entry.extend_from_slice(data);
+        entry.resize(entry.len().div_ceil(512) * 512, 0);
+        entry
+    }
+
+    /// Wraps `payload` as a single uncompressed (stored) DEFLATE block, then
+    /// a minimal gzip member around that, so tests don't need a real
+    /// compressor to exercise `gunzip`/`inflate`.
+    fn gzip_stored(payload: &[u8]) -> Vec<u8> {
+        let mut deflate = vec![0x01u8]; // BFINAL=1, BTYPE=00 (stored)
+        let len = payload.len() as u16;
+        deflate.extend_from_slice(&len.to_le_bytes());
+        deflate.extend_from_slice(&(!len).to_le_bytes());
+        deflate.extend_from_slice(payload);
+
+        let mut gzip = vec![0x1f, 0x8b, 0x08, 0x00, 0, 0, 0, 0, 0x00, 0xff];
+        gzip.extend_from_slice(&deflate);
+        gzip
+    }
+
+    #[test]
+    fn mounts_a_crate_tarball_and_strips_the_name_version_prefix() {
+        let mut tar = Vec::new();
+        tar.extend(tar_entry("demo-0.1.0/Cargo.toml", b"[package]\n"));
+        tar.extend(tar_entry("demo-0.1.0/src/lib.rs", b"fn main() {}"));
+
+        let vfs = mount_crate_archive(gzip_stored(&tar)).expect("mounts");
+
+        let mut paths = vfs.list();
+        paths.sort();
+        assert_eq!(paths, vec!["Cargo.toml", "src/lib.rs"]);
+        assert_eq!(vfs.read("Cargo.toml").unwrap(), b"[package]\n");
+        assert_eq!(vfs.read("src/lib.rs").unwrap(), b"fn main() {}");
+    }
+
+    #[test]
+    fn rejects_non_gzip_input() {
+        assert!(gunzip(&[0, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_fextra_instead_of_panicking() {
+        // FEXTRA set, XLEN says 5000 bytes follow, but the header ends
+        // immediately after — must return Err, not panic slicing `bytes`.
+        let mut bytes = vec![0x1f, 0x8b, 0x08, FEXTRA, 0, 0, 0, 0, 0x00, 0xff];
+        bytes.extend_from_slice(&5000u16.to_le_bytes());
+        assert!(gunzip(&bytes).is_err());
+    }
+}
@@ -1,13 +1,21 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+mod archive;
+mod markdown;
+mod preview;
+mod tree;
+mod zip;
+
+use preview::FileKind;
 
 use gloo_net::http::Request;
 use ratatui::{
-    text::Line,
+    text::{Line, Span},
     widgets::{Borders, Wrap},
 };
 use ratzilla::ratatui::{
     layout::{Alignment, Constraint, Layout},
-    style::{Color, Stylize},
+    style::{Color, Style, Stylize},
     text::Text,
     widgets::{Block, BorderType, Paragraph},
     Frame, Terminal,
@@ -49,6 +57,24 @@ fn main() -> Result<(), JsError> {
 pub struct FilePreview {
     path: String,
     preview: String,
+    kind: FileKind,
+    size: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum Mode {
+    #[default]
+    Browse,
+    Search,
+}
+
+#[derive(Clone, Debug)]
+struct SearchResult {
+    path: String,
+    line_no: usize,
+    line: String,
+    match_start: usize,
+    match_len: usize,
 }
 
 #[derive(Default)]
@@ -58,12 +84,25 @@ struct App {
     vfs: RefCell<Option<InMemoryVfs>>,
     previews: RefCell<Vec<FilePreview>>,
     status: RefCell<String>,
+    show_markdown: RefCell<bool>,
+    collapsed: RefCell<HashSet<String>>,
+    selected: RefCell<usize>,
+    scroll: RefCell<u16>,
+    viewing: RefCell<Option<String>>,
+    mode: RefCell<Mode>,
+    query: RefCell<String>,
+    search_results: RefCell<Vec<SearchResult>>,
 }
 
 impl App {
     fn new() -> Self {
         Self {
-            status: RefCell::new("Press U to upload a crate".into()),
+            status: RefCell::new(
+                "Press U to upload a crate, or C to import a .crate file. \
+                 Up/Down/Left/Right navigate the tree, Enter opens a file, Esc closes it. \
+                 / searches the crate."
+                    .into(),
+            ),
             previews: RefCell::new(Vec::new()),
             vfs: RefCell::new(None),
             ..Default::default()
@@ -75,11 +114,19 @@ impl App {
         if let Some(vfs) = &*self.vfs.borrow() {
             for path in vfs.list() {
                 let bytes = vfs.read(&path).unwrap_or_default();
-                let s = String::from_utf8_lossy(&bytes).replace('\n', " ");
-                let short = s.chars().take(30).collect::<String>();
+                let kind = preview::detect_kind(&bytes);
+                let short = match kind {
+                    FileKind::Binary => format!("<binary, {}>", preview::human_size(bytes.len())),
+                    FileKind::Text => {
+                        let s = String::from_utf8_lossy(&bytes).replace('\n', " ");
+                        s.chars().take(30).collect::<String>()
+                    }
+                };
                 previews.push(FilePreview {
                     path,
                     preview: short,
+                    kind,
+                    size: bytes.len(),
                 });
             }
         }
@@ -90,7 +137,80 @@ impl App {
             ));
         }
     }
+    fn reset_navigation(&self) {
+        self.collapsed.borrow_mut().clear();
+        self.selected.replace(0);
+        self.scroll.replace(0);
+        self.viewing.replace(None);
+        self.mode.replace(Mode::Browse);
+        self.query.borrow_mut().clear();
+        self.search_results.borrow_mut().clear();
+    }
+
+    fn mode(&self) -> Mode {
+        *self.mode.borrow()
+    }
+
+    /// Scans every non-binary file for lines containing the current query,
+    /// case-insensitively.
+    fn run_search(&self) {
+        let mut results = Vec::new();
+        let query = self.query.borrow().to_lowercase();
+        if !query.is_empty() {
+            if let Some(vfs) = &*self.vfs.borrow() {
+                let mut paths = vfs.list();
+                paths.sort();
+                for path in paths {
+                    let bytes = vfs.read(&path).unwrap_or_default();
+                    if preview::detect_kind(&bytes) == FileKind::Binary {
+                        continue;
+                    }
+                    let text = String::from_utf8_lossy(&bytes);
+                    for (i, line) in text.lines().enumerate() {
+                        if let Some((start, len)) = find_case_insensitive(line, &query) {
+                            results.push(SearchResult {
+                                path: path.clone(),
+                                line_no: i + 1,
+                                line: line.to_string(),
+                                match_start: start,
+                                match_len: len,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        self.search_results.replace(results);
+        self.selected.replace(0);
+    }
+
+    fn tree_rows(&self) -> Vec<tree::Row> {
+        let vfs = self.vfs.borrow();
+        match &*vfs {
+            Some(vfs) => {
+                let mut paths = vfs.list();
+                paths.sort();
+                tree::flatten(&paths, &self.collapsed.borrow())
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn readme_path(&self) -> Option<String> {
+        let vfs = self.vfs.borrow();
+        let vfs = vfs.as_ref()?;
+        vfs.list()
+            .into_iter()
+            .find(|p| p.eq_ignore_ascii_case("README.md") || p.to_lowercase().ends_with("/readme.md"))
+    }
+
     fn render(&self, frame: &mut Frame) {
+        if self.viewing.borrow().is_some() {
+            let viewer = generate_content_viewer(self);
+            frame.render_widget(viewer, frame.area());
+            return;
+        }
+
         let chunks = Layout::vertical([
             Constraint::Length(10),
             Constraint::Length(10),
@@ -99,7 +219,7 @@ impl App {
         .split(frame.area());
 
         let counter = self.counter.borrow();
-        let paragraph = generate_paragraph(counter);
+        let paragraph = generate_paragraph(counter, self.vfs.borrow().is_some());
 
         frame.render_widget(paragraph, chunks[0]);
 
@@ -107,12 +227,87 @@ impl App {
         let loaded_paragraph = generate_loaded_text(loaded_text);
         frame.render_widget(loaded_paragraph, chunks[1]);
 
-        let loaded_files = generate_file_previews(self);
-        frame.render_widget(loaded_files, chunks[2]);
+        if self.mode() == Mode::Search {
+            let search = generate_search_pane(self);
+            frame.render_widget(search, chunks[2]);
+        } else if *self.show_markdown.borrow() {
+            let readme = generate_markdown_pane(self);
+            frame.render_widget(readme, chunks[2]);
+        } else {
+            let loaded_files = generate_file_tree(self);
+            frame.render_widget(loaded_files, chunks[2]);
+        }
     }
 
     async fn handle_events(&self, key_event: KeyEvent) {
         match key_event.code {
+            KeyCode::Char('/') if self.mode() == Mode::Browse
+                && self.viewing.borrow().is_none()
+                && self.vfs.borrow().is_some() =>
+            {
+                self.mode.replace(Mode::Search);
+                self.query.borrow_mut().clear();
+                self.search_results.borrow_mut().clear();
+                self.selected.replace(0);
+            }
+            KeyCode::Esc if self.mode() == Mode::Search => {
+                self.mode.replace(Mode::Browse);
+                self.selected.replace(0);
+            }
+            KeyCode::Backspace if self.mode() == Mode::Search => {
+                self.query.borrow_mut().pop();
+                self.run_search();
+            }
+            KeyCode::Enter if self.mode() == Mode::Search => {
+                let results = self.search_results.borrow();
+                if let Some(result) = results.get(*self.selected.borrow()) {
+                    let path = result.path.clone();
+                    let line_no = result.line_no;
+                    drop(results);
+                    self.viewing.replace(Some(path));
+                    self.scroll.replace(line_no.saturating_sub(1) as u16);
+                    self.mode.replace(Mode::Browse);
+                }
+            }
+            KeyCode::Up if self.mode() == Mode::Search => {
+                let mut selected = self.selected.borrow_mut();
+                *selected = selected.saturating_sub(1);
+            }
+            KeyCode::Down if self.mode() == Mode::Search => {
+                let results = self.search_results.borrow();
+                let mut selected = self.selected.borrow_mut();
+                if *selected + 1 < results.len() {
+                    *selected += 1;
+                }
+            }
+            KeyCode::Char(c) if self.mode() == Mode::Search => {
+                self.query.borrow_mut().push(c);
+                self.run_search();
+            }
+            KeyCode::Left
+                if self.mode() == Mode::Browse
+                    && self.viewing.borrow().is_none()
+                    && self.vfs.borrow().is_some() =>
+            {
+                let rows = self.tree_rows();
+                if let Some(row) = rows.get(*self.selected.borrow()) {
+                    if row.is_dir() {
+                        self.collapsed.borrow_mut().insert(row.path().to_string());
+                    }
+                }
+            }
+            KeyCode::Right
+                if self.mode() == Mode::Browse
+                    && self.viewing.borrow().is_none()
+                    && self.vfs.borrow().is_some() =>
+            {
+                let rows = self.tree_rows();
+                if let Some(row) = rows.get(*self.selected.borrow()) {
+                    if row.is_dir() {
+                        self.collapsed.borrow_mut().remove(row.path());
+                    }
+                }
+            }
             KeyCode::Left => {
                 let mut counter = self.counter.borrow_mut();
                 *counter = counter.saturating_sub(1);
@@ -129,12 +324,80 @@ impl App {
                 Ok(vfs) => {
                     self.vfs.replace(Some(vfs));
                     self.rebuild_previews();
+                    self.reset_navigation();
                 }
                 Err(e) => {
                     self.status
                         .replace(format!("Failed to load crate: {:?}", e));
                 }
             },
+            KeyCode::Char('c') => match gather_crate_archive_bytes().await {
+                Ok(bytes) => match archive::mount_crate_archive(bytes) {
+                    Ok(vfs) => {
+                        self.vfs.replace(Some(vfs));
+                        self.rebuild_previews();
+                        self.reset_navigation();
+                    }
+                    Err(e) => {
+                        self.status
+                            .replace(format!("Failed to unpack .crate: {:?}", e));
+                    }
+                },
+                Err(e) => {
+                    self.status.replace(format!("Failed to read .crate: {:?}", e));
+                }
+            },
+            KeyCode::Up if self.viewing.borrow().is_none() && self.vfs.borrow().is_some() => {
+                let mut selected = self.selected.borrow_mut();
+                *selected = selected.saturating_sub(1);
+            }
+            KeyCode::Down if self.viewing.borrow().is_none() && self.vfs.borrow().is_some() => {
+                let rows = self.tree_rows();
+                let mut selected = self.selected.borrow_mut();
+                if *selected + 1 < rows.len() {
+                    *selected += 1;
+                }
+            }
+            KeyCode::Enter if self.vfs.borrow().is_some() => {
+                let rows = self.tree_rows();
+                if let Some(row) = rows.get(*self.selected.borrow()) {
+                    if !row.is_dir() {
+                        self.viewing.replace(Some(row.path().to_string()));
+                        self.scroll.replace(0);
+                    }
+                }
+            }
+            KeyCode::Esc if self.viewing.borrow().is_some() => {
+                self.viewing.replace(None);
+            }
+            KeyCode::PageUp if self.viewing.borrow().is_some() => {
+                let mut scroll = self.scroll.borrow_mut();
+                *scroll = scroll.saturating_sub(10);
+            }
+            KeyCode::PageDown if self.viewing.borrow().is_some() => {
+                let mut scroll = self.scroll.borrow_mut();
+                *scroll = scroll.saturating_add(10);
+            }
+            KeyCode::Home if self.viewing.borrow().is_some() => {
+                self.scroll.replace(0);
+            }
+            KeyCode::End if self.viewing.borrow().is_some() => {
+                if let Some(path) = &*self.viewing.borrow() {
+                    if let Some(vfs) = &*self.vfs.borrow() {
+                        let bytes = vfs.read(path).unwrap_or_default();
+                        let lines = String::from_utf8_lossy(&bytes).lines().count();
+                        self.scroll.replace(lines as u16);
+                    }
+                }
+            }
+            KeyCode::Char('m') => {
+                if self.readme_path().is_some() {
+                    let mut show = self.show_markdown.borrow_mut();
+                    *show = !*show;
+                } else {
+                    self.status.replace("No README.md in the loaded crate".into());
+                }
+            }
             KeyCode::Char('e') => {
                 if let Some(vfs) = &*self.vfs.borrow() {
                     if let Err(e) = export_as_zip(vfs) {
@@ -147,17 +410,25 @@ impl App {
     }
 }
 
-fn generate_paragraph(counter: std::cell::Ref<'_, u8>) -> Paragraph<'_> {
+fn generate_paragraph(counter: std::cell::Ref<'_, u8>, crate_loaded: bool) -> Paragraph<'_> {
     let block = Block::bordered()
         .title("generic-rat")
         .title_alignment(Alignment::Center)
         .border_type(BorderType::Rounded);
 
-    let text = format!(
-        "This is a Ratzilla template.\n\
+    let text = if crate_loaded {
+        format!(
+            "This is a Ratzilla template.\n\
+             Left/Right collapse and expand the selected directory in the tree below.\n\
+             Counter: {counter}",
+        )
+    } else {
+        format!(
+            "This is a Ratzilla template.\n\
              Press left and right to increment and decrement the counter respectively.\n\
              Counter: {counter}",
-    );
+        )
+    };
 
     let paragraph = Paragraph::new(text)
         .block(block)
@@ -185,15 +456,50 @@ fn generate_loaded_text(text: std::cell::Ref<'_, Option<String>>) -> Paragraph<'
     paragraph
 }
 
-fn generate_file_previews<'a>(app: &'a App) -> Paragraph<'a> {
-    let mut lines: Vec<Line> = Vec::with_capacity(app.previews.borrow().len() + 2);
+fn generate_file_tree(app: &App) -> Paragraph<'static> {
+    let selected = *app.selected.borrow();
+    let rows = app.tree_rows();
+    let previews = app.previews.borrow();
 
+    let mut lines: Vec<Line> = Vec::with_capacity(rows.len() + 2);
     lines.push(Line::from(app.status.borrow().clone()));
     lines.push(Line::from(" "));
 
-    for fp in &*app.previews.borrow() {
-        let line = format!("{}: {}", fp.path, fp.preview);
-        lines.push(Line::from(line));
+    for (i, row) in rows.iter().enumerate() {
+        let indent = "  ".repeat(row.depth());
+        let file_preview = match row {
+            tree::Row::File { path, .. } => previews.iter().find(|fp| &fp.path == path),
+            tree::Row::Dir { .. } => None,
+        };
+        let binary = file_preview
+            .map(|fp| fp.kind == FileKind::Binary)
+            .unwrap_or(false);
+        let text = match row {
+            tree::Row::Dir { name, expanded, .. } => {
+                let marker = if *expanded { "\u{25be}" } else { "\u{25b8}" };
+                format!("{indent}{marker} {name}/")
+            }
+            tree::Row::File { name, .. } => match file_preview {
+                Some(fp) if fp.kind == FileKind::Binary => {
+                    format!("{indent}  {name} <binary, {}>", preview::human_size(fp.size))
+                }
+                Some(fp) => format!(
+                    "{indent}  {name}  {} ({})",
+                    fp.preview,
+                    preview::human_size(fp.size)
+                ),
+                None => format!("{indent}  {name}"),
+            },
+        };
+
+        let line = if i == selected {
+            Line::from(text).bg(Color::Blue).fg(Color::White)
+        } else if binary {
+            Line::from(text).fg(Color::DarkGray)
+        } else {
+            Line::from(text)
+        };
+        lines.push(line);
     }
 
     Paragraph::new(lines)
@@ -205,6 +511,128 @@ fn generate_file_previews<'a>(app: &'a App) -> Paragraph<'a> {
         .wrap(Wrap { trim: true })
 }
 
+fn generate_search_pane(app: &App) -> Paragraph<'static> {
+    let selected = *app.selected.borrow();
+    let results = app.search_results.borrow();
+
+    let mut lines: Vec<Line> = Vec::with_capacity(results.len() + 2);
+    lines.push(Line::from(format!("/{}", app.query.borrow())));
+    lines.push(Line::from(format!("{} matches. Esc to cancel.", results.len())));
+
+    for (i, result) in results.iter().enumerate() {
+        let before = &result.line[..result.match_start];
+        let matched = &result.line[result.match_start..result.match_start + result.match_len];
+        let after = &result.line[result.match_start + result.match_len..];
+
+        let spans = vec![
+            Span::raw(format!("{}:{}: ", result.path, result.line_no)),
+            Span::raw(before.to_string()),
+            Span::styled(
+                matched.to_string(),
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            ),
+            Span::raw(after.to_string()),
+        ];
+
+        let line = Line::from(spans);
+        let line = if i == selected {
+            line.bg(Color::Blue)
+        } else {
+            line
+        };
+        lines.push(line);
+    }
+
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Search")
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: true })
+}
+
+fn generate_content_viewer(app: &App) -> Paragraph<'static> {
+    let path = app.viewing.borrow().clone().unwrap_or_default();
+    let bytes = app
+        .vfs
+        .borrow()
+        .as_ref()
+        .and_then(|vfs| vfs.read(&path))
+        .unwrap_or_default();
+
+    let body: Text<'static> = match preview::detect_kind(&bytes) {
+        FileKind::Binary => {
+            Text::from(format!("<binary file, {}>", preview::human_size(bytes.len())))
+        }
+        FileKind::Text => {
+            let source = String::from_utf8_lossy(&bytes).into_owned();
+            if path.to_lowercase().ends_with(".md") {
+                markdown::render(&source)
+            } else {
+                Text::from(source)
+            }
+        }
+    };
+
+    Paragraph::new(body)
+        .block(
+            Block::default()
+                .title(path)
+                .borders(Borders::ALL),
+        )
+        .scroll((*app.scroll.borrow(), 0))
+        .wrap(Wrap { trim: false })
+}
+
+fn generate_markdown_pane(app: &App) -> Paragraph<'static> {
+    let source = app
+        .readme_path()
+        .and_then(|path| app.vfs.borrow().as_ref()?.read(&path))
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default();
+
+    Paragraph::new(markdown::render(&source))
+        .block(
+            Block::default()
+                .title("README.md")
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: false })
+}
+
+/// Case-insensitive substring search that returns byte offsets into the
+/// original (not lower-cased) `haystack`, so the result always lands on its
+/// char boundaries even when case folding changes a character's byte length
+/// (e.g. `İ` lower-cases to two code points).
+fn find_case_insensitive(haystack: &str, needle_lower: &str) -> Option<(usize, usize)> {
+    if needle_lower.is_empty() {
+        return None;
+    }
+    let needle_len = needle_lower.chars().count();
+    let positions: Vec<(usize, char)> = haystack.char_indices().collect();
+
+    for start in 0..positions.len() {
+        if start + needle_len > positions.len() {
+            break;
+        }
+        let window = &positions[start..start + needle_len];
+        let matches = window
+            .iter()
+            .zip(needle_lower.chars())
+            .all(|(&(_, hc), nc)| hc.to_lowercase().eq(nc.to_lowercase()));
+        if matches {
+            let byte_start = window[0].0;
+            let byte_end = positions
+                .get(start + needle_len)
+                .map(|&(pos, _)| pos)
+                .unwrap_or(haystack.len());
+            return Some((byte_start, byte_end - byte_start));
+        }
+    }
+    None
+}
+
 async fn load_text(file_path: &'static str) -> Option<String> {
     let text = match Request::get(file_path).send().await {
         Ok(resp) => resp
@@ -223,7 +651,7 @@ pub trait Vfs {
 }
 
 pub struct InMemoryVfs {
-    files: std::collections::BTreeMap<String, Vec<u8>>,
+    pub(crate) files: std::collections::BTreeMap<String, Vec<u8>>,
 }
 
 impl Vfs for InMemoryVfs {
@@ -250,8 +678,11 @@ extern "C" {
     #[wasm_bindgen(js_namespace = window, js_name = pickRustCrateFallback)]
     async fn pick_rust_crate_fallback() -> JsValue;
 
-    #[wasm_bindgen(js_namespace = window, js_name = downloadAsZip)]
-    fn download_as_zip(files: JsValue);
+    #[wasm_bindgen(js_namespace = window, js_name = saveZipBlob)]
+    fn save_zip_blob(bytes: Uint8Array, filename: &str);
+
+    #[wasm_bindgen(js_namespace = window, js_name = pickCrateArchive)]
+    async fn pick_crate_archive() -> JsValue;
 }
 
 #[derive(Clone)]
@@ -285,6 +716,12 @@ async fn gather_files() -> Result<Vec<FileEntry>, JsValue> {
     Ok(out)
 }
 
+async fn gather_crate_archive_bytes() -> Result<Vec<u8>, JsValue> {
+    let js = pick_crate_archive().await;
+    let bytes: Uint8Array = js.dyn_into()?;
+    Ok(bytes.to_vec())
+}
+
 pub async fn mount_picked_crate() -> Result<InMemoryVfs, JsValue> {
     let files = gather_files().await?;
     let mut map = std::collections::BTreeMap::new();
@@ -295,22 +732,56 @@ pub async fn mount_picked_crate() -> Result<InMemoryVfs, JsValue> {
 }
 
 pub fn export_as_zip(vfs: &impl Vfs) -> Result<(), JsValue> {
-    let files = Array::new();
+    let bytes = zip::build_zip(vfs, zip::ZipCompression::Deflate);
+    save_zip_blob(Uint8Array::from(bytes.as_slice()), "crate-export.zip");
+    Ok(())
+}
 
-    for p in vfs.list() {
-        let bytes = vfs.read(&p).unwrap_or_default();
+#[cfg(test)]
+mod search_tests {
+    use super::*;
 
-        let rec = js_sys::Object::new();
-        // path
-        js_sys::Reflect::set(&rec, &JsValue::from_str("path"), &JsValue::from_str(&p))?;
-        // bytes (Uint8Array)
-        let u8 = Uint8Array::from(bytes.as_slice());
-        js_sys::Reflect::set(&rec, &JsValue::from_str("bytes"), &u8.into())?;
+    #[test]
+    fn finds_an_ascii_substring_case_insensitively() {
+        let (start, len) = find_case_insensitive("Hello World", "world").unwrap();
+        assert_eq!(&"Hello World"[start..start + len], "World");
+    }
 
-        // Push JsValue into the Array explicitly:
-        files.push(&JsValue::from(rec));
+    #[test]
+    fn returns_none_when_the_needle_is_absent() {
+        assert!(find_case_insensitive("Hello World", "xyz").is_none());
     }
 
-    download_as_zip(files.into());
-    Ok(())
+    #[test]
+    fn empty_needle_never_matches() {
+        assert!(find_case_insensitive("Hello", "").is_none());
+    }
+
+    #[test]
+    fn handles_case_folding_that_changes_byte_length() {
+        // 'İ' (U+0130) lowercases to "i\u{307}" (2 code points, 3 bytes),
+        // one byte longer than the original 2-byte UTF-8 character.
+        let haystack = "İ foo";
+        let (start, len) = find_case_insensitive(haystack, "foo").unwrap();
+        assert_eq!(&haystack[start..start + len], "foo");
+    }
+
+    #[test]
+    fn run_search_finds_matches_across_files_and_skips_binary() {
+        let mut files = std::collections::BTreeMap::new();
+        files.insert("src/lib.rs".to_string(), b"fn main() {}\n// TODO fix\n".to_vec());
+        files.insert("bin.dat".to_string(), vec![0u8, 1, 2, 3]);
+        let app = App {
+            vfs: RefCell::new(Some(InMemoryVfs { files })),
+            ..App::new()
+        };
+
+        app.query.replace("todo".to_string());
+        app.run_search();
+
+        let results = app.search_results.borrow();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "src/lib.rs");
+        assert_eq!(results[0].line_no, 2);
+    }
 }
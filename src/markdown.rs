@@ -0,0 +1,208 @@
+//! A small Markdown-to-`ratatui::Text` renderer, just enough of CommonMark
+//! to make a crate's `README.md` pleasant to read in a terminal pane:
+//! ATX headings, fenced code blocks, bullet/numbered lists, and
+//! `**bold**`/`*italic*`/`` `code` `` inline spans.
+
+use ratzilla::ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+};
+
+const HEADING_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::LightCyan,
+    Color::Yellow,
+    Color::LightYellow,
+    Color::Green,
+    Color::LightGreen,
+];
+
+/// Renders a Markdown document as a `Text` suitable for a bordered
+/// `Paragraph`.
+pub fn render(source: &str) -> Text<'static> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+
+    for raw_line in source.lines() {
+        let trimmed = raw_line.trim_start();
+
+        if let Some(rest) = trimmed.strip_prefix("```") {
+            in_code_block = !in_code_block;
+            let _ = rest; // language tag is not rendered
+            lines.push(Line::from(Span::styled(
+                "```",
+                Style::default().fg(Color::DarkGray),
+            )));
+            continue;
+        }
+
+        if in_code_block {
+            lines.push(Line::from(Span::styled(
+                raw_line.to_string(),
+                Style::default().fg(Color::White).bg(Color::Rgb(30, 30, 30)),
+            )));
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            lines.push(Line::from(""));
+            continue;
+        }
+
+        if let Some(heading) = parse_heading(trimmed) {
+            lines.push(heading);
+            continue;
+        }
+
+        if let Some(item) = parse_bullet(trimmed) {
+            lines.push(item);
+            continue;
+        }
+
+        if let Some(item) = parse_numbered(trimmed) {
+            lines.push(item);
+            continue;
+        }
+
+        lines.push(Line::from(parse_inline(trimmed)));
+    }
+
+    Text::from(lines)
+}
+
+fn parse_heading(line: &str) -> Option<Line<'static>> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 || !line[level..].starts_with(' ') {
+        return None;
+    }
+    let text = line[level..].trim_start().to_string();
+    let color = HEADING_COLORS[level - 1];
+    Some(Line::from(Span::styled(
+        text,
+        Style::default()
+            .fg(color)
+            .add_modifier(Modifier::BOLD),
+    )))
+}
+
+fn parse_bullet(line: &str) -> Option<Line<'static>> {
+    let rest = line.strip_prefix("- ").or_else(|| line.strip_prefix("* "))?;
+    let mut spans = vec![Span::raw("  \u{2022} ")];
+    spans.extend(parse_inline(rest));
+    Some(Line::from(spans))
+}
+
+fn parse_numbered(line: &str) -> Option<Line<'static>> {
+    let digits: String = line.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        return None;
+    }
+    let rest = line.strip_prefix(&digits)?.strip_prefix(". ")?;
+    let mut spans = vec![Span::raw(format!("  {digits}. "))];
+    spans.extend(parse_inline(rest));
+    Some(Line::from(spans))
+}
+
+/// Converts `**bold**`, `*italic*`, and `` `code` `` spans within a single
+/// line of inline text.
+fn parse_inline(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+
+    let flush_plain = |plain: &mut String, spans: &mut Vec<Span<'static>>| {
+        if !plain.is_empty() {
+            spans.push(Span::raw(std::mem::take(plain)));
+        }
+    };
+
+    while i < chars.len() {
+        if chars[i] == '*' && chars.get(i + 1) == Some(&'*') {
+            if let Some(end) = find_closing(&chars, i + 2, "**") {
+                flush_plain(&mut plain, &mut spans);
+                let inner: String = chars[i + 2..end].iter().collect();
+                spans.push(Span::styled(inner, Style::default().add_modifier(Modifier::BOLD)));
+                i = end + 2;
+                continue;
+            }
+        } else if chars[i] == '*' {
+            if let Some(end) = find_closing(&chars, i + 1, "*") {
+                flush_plain(&mut plain, &mut spans);
+                let inner: String = chars[i + 1..end].iter().collect();
+                spans.push(Span::styled(inner, Style::default().add_modifier(Modifier::ITALIC)));
+                i = end + 1;
+                continue;
+            }
+        } else if chars[i] == '`' {
+            if let Some(end) = find_closing(&chars, i + 1, "`") {
+                flush_plain(&mut plain, &mut spans);
+                let inner: String = chars[i + 1..end].iter().collect();
+                spans.push(Span::styled(
+                    inner,
+                    Style::default().fg(Color::Magenta).bg(Color::Rgb(30, 30, 30)),
+                ));
+                i = end + 1;
+                continue;
+            }
+        }
+        plain.push(chars[i]);
+        i += 1;
+    }
+    flush_plain(&mut plain, &mut spans);
+    spans
+}
+
+fn find_closing(chars: &[char], from: usize, marker: &str) -> Option<usize> {
+    let marker: Vec<char> = marker.chars().collect();
+    let mut i = from;
+    while i + marker.len() <= chars.len() {
+        if chars[i..i + marker.len()] == marker[..] {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain_text(line: &Line<'static>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn renders_a_heading() {
+        let text = render("# Title");
+        assert_eq!(plain_text(&text.lines[0]), "Title");
+    }
+
+    #[test]
+    fn renders_bullet_and_numbered_lists() {
+        let text = render("- one\n1. two");
+        assert_eq!(plain_text(&text.lines[0]), "  \u{2022} one");
+        assert_eq!(plain_text(&text.lines[1]), "  1. two");
+    }
+
+    #[test]
+    fn renders_inline_bold_italic_and_code() {
+        let text = render("**bold** *italic* `code`");
+        assert_eq!(plain_text(&text.lines[0]), "bold italic code");
+    }
+
+    #[test]
+    fn code_fences_render_as_marker_lines_around_the_code() {
+        let text = render("```\nlet x = 1;\n```");
+        assert_eq!(text.lines.len(), 3);
+        assert_eq!(plain_text(&text.lines[1]), "let x = 1;");
+    }
+
+    #[test]
+    fn blank_lines_are_preserved() {
+        let text = render("one\n\ntwo");
+        assert_eq!(text.lines.len(), 3);
+        assert_eq!(plain_text(&text.lines[1]), "");
+    }
+}
@@ -0,0 +1,97 @@
+//! Content sniffing for the "Uploaded Crate" preview panel: tell binary
+//! files (rlibs, images, vendored blobs) apart from text so they don't
+//! render as mojibake.
+
+const SAMPLE_SIZE: usize = 8 * 1024;
+
+/// Whether a file's contents look like text or binary data.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileKind {
+    Text,
+    Binary,
+}
+
+/// Samples the first ~8 KiB of `bytes` the way a file server would: a NUL
+/// byte, or a high ratio of non-printable/invalid-UTF-8 bytes, marks the
+/// file as binary.
+pub fn detect_kind(bytes: &[u8]) -> FileKind {
+    let sample = &bytes[..bytes.len().min(SAMPLE_SIZE)];
+    if sample.is_empty() {
+        return FileKind::Text;
+    }
+    if sample.contains(&0) {
+        return FileKind::Binary;
+    }
+
+    let non_printable = sample
+        .iter()
+        .filter(|&&b| b != b'\n' && b != b'\r' && b != b'\t' && (b < 0x20 || b == 0x7f))
+        .count();
+    let mut invalid_utf8 = 0;
+    let mut rest = sample;
+    while let Err(err) = std::str::from_utf8(rest) {
+        match err.error_len() {
+            Some(len) => {
+                invalid_utf8 += len;
+                rest = &rest[err.valid_up_to() + len..];
+            }
+            // A multi-byte character cut short by the end of the sample.
+            None => break,
+        }
+    }
+
+    if (non_printable + invalid_utf8) * 20 > sample.len() {
+        FileKind::Binary
+    } else {
+        FileKind::Text
+    }
+}
+
+/// Formats a byte count the way file browsers do, e.g. `12.3 KiB`.
+pub fn human_size(size: usize) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} {}", UNITS[unit])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input_is_text() {
+        assert_eq!(detect_kind(&[]), FileKind::Text);
+    }
+
+    #[test]
+    fn nul_byte_marks_binary() {
+        assert_eq!(detect_kind(b"hello\0world"), FileKind::Binary);
+    }
+
+    #[test]
+    fn invalid_utf8_marks_binary() {
+        assert_eq!(detect_kind(&[0xff, 0xfe, 0xfd, 0xfc]), FileKind::Binary);
+    }
+
+    #[test]
+    fn plain_source_is_text() {
+        assert_eq!(detect_kind(b"fn main() {\n    println!(\"hi\");\n}\n"), FileKind::Text);
+    }
+
+    #[test]
+    fn human_size_picks_the_right_unit() {
+        assert_eq!(human_size(0), "0 B");
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
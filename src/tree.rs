@@ -0,0 +1,135 @@
+//! Turns a flat list of VFS paths into a collapsible directory hierarchy,
+//! the way a real crate's `src/`, `tests/`, `examples/` layout should be
+//! browsed.
+
+use std::collections::{BTreeMap, HashSet};
+
+/// One visible row in the flattened tree, in display order.
+pub enum Row {
+    Dir {
+        path: String,
+        name: String,
+        depth: usize,
+        expanded: bool,
+    },
+    File {
+        path: String,
+        name: String,
+        depth: usize,
+    },
+}
+
+impl Row {
+    pub fn path(&self) -> &str {
+        match self {
+            Row::Dir { path, .. } => path,
+            Row::File { path, .. } => path,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Row::Dir { .. })
+    }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            Row::Dir { depth, .. } => *depth,
+            Row::File { depth, .. } => *depth,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+    is_file: bool,
+}
+
+fn build_tree(paths: &[String]) -> Node {
+    let mut root = Node::default();
+    for path in paths {
+        let segments: Vec<&str> = path.split('/').collect();
+        let mut node = &mut root;
+        for (i, segment) in segments.iter().enumerate() {
+            node = node.children.entry(segment.to_string()).or_default();
+            if i == segments.len() - 1 {
+                node.is_file = true;
+            }
+        }
+    }
+    root
+}
+
+/// Flattens `paths` into display rows, skipping the children of any
+/// directory path present in `collapsed`.
+pub fn flatten(paths: &[String], collapsed: &HashSet<String>) -> Vec<Row> {
+    let root = build_tree(paths);
+    let mut rows = Vec::new();
+    flatten_node(&root, "", 0, collapsed, &mut rows);
+    rows
+}
+
+fn flatten_node(node: &Node, prefix: &str, depth: usize, collapsed: &HashSet<String>, rows: &mut Vec<Row>) {
+    for (name, child) in &node.children {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}/{name}")
+        };
+
+        if child.is_file && child.children.is_empty() {
+            rows.push(Row::File {
+                path,
+                name: name.clone(),
+                depth,
+            });
+        } else {
+            let expanded = !collapsed.contains(&path);
+            rows.push(Row::Dir {
+                path: path.clone(),
+                name: name.clone(),
+                depth,
+                expanded,
+            });
+            if expanded {
+                flatten_node(child, &path, depth + 1, collapsed, rows);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(rows: &[Row]) -> Vec<&str> {
+        rows.iter().map(Row::path).collect()
+    }
+
+    #[test]
+    fn flattens_nested_directories_in_sorted_order() {
+        let input = vec![
+            "src/lib.rs".to_string(),
+            "Cargo.toml".to_string(),
+            "src/tree.rs".to_string(),
+        ];
+        let rows = flatten(&input, &HashSet::new());
+        assert_eq!(paths(&rows), vec!["Cargo.toml", "src", "src/lib.rs", "src/tree.rs"]);
+        assert_eq!(rows[1].depth(), 0);
+        assert_eq!(rows[2].depth(), 1);
+        assert!(rows[1].is_dir());
+        assert!(!rows[2].is_dir());
+    }
+
+    #[test]
+    fn collapsed_directories_hide_their_children() {
+        let input = vec!["src/lib.rs".to_string(), "src/tree.rs".to_string()];
+        let collapsed: HashSet<String> = ["src".to_string()].into_iter().collect();
+        let rows = flatten(&input, &collapsed);
+        assert_eq!(paths(&rows), vec!["src"]);
+        match &rows[0] {
+            Row::Dir { expanded, .. } => assert!(!expanded),
+            Row::File { .. } => panic!("expected a dir row"),
+        }
+    }
+}
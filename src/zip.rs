@@ -0,0 +1,235 @@
+//! Minimal ZIP writer so `export_as_zip` can hand the browser a real archive
+//! instead of delegating the packing to JS.
+
+use crate::Vfs;
+
+const LOCAL_FILE_HEADER_SIG: u32 = 0x0403_4b50;
+const CENTRAL_DIR_HEADER_SIG: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIR_SIG: u32 = 0x0605_4b50;
+
+const METHOD_STORE: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+
+/// Whether entries are stored as-is or DEFLATE-compressed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ZipCompression {
+    // Not picked by `export_as_zip` today, but part of `build_zip`'s public
+    // API (and exercised directly in this module's tests).
+    #[allow(dead_code)]
+    Store,
+    Deflate,
+}
+
+struct CentralDirEntry {
+    method: u16,
+    crc32: u32,
+    compressed_size: u32,
+    uncompressed_size: u32,
+    name: String,
+    local_header_offset: u32,
+}
+
+/// Builds a ZIP archive from every file in `vfs`, ready to hand to the
+/// browser for download.
+pub fn build_zip(vfs: &impl Vfs, compression: ZipCompression) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut central_dir = Vec::new();
+
+    for path in vfs.list() {
+        let data = vfs.read(&path).unwrap_or_default();
+        let crc = crc32(&data);
+
+        let (method, payload) = match compression {
+            ZipCompression::Store => (METHOD_STORE, data.clone()),
+            ZipCompression::Deflate => {
+                let compressed = miniz_oxide::deflate::compress_to_vec(&data, 6);
+                if compressed.len() < data.len() {
+                    (METHOD_DEFLATE, compressed)
+                } else {
+                    (METHOD_STORE, data.clone())
+                }
+            }
+        };
+
+        let local_header_offset = out.len() as u32;
+        let name_bytes = path.as_bytes();
+
+        out.extend_from_slice(&LOCAL_FILE_HEADER_SIG.to_le_bytes());
+        out.extend_from_slice(&20u16.to_le_bytes()); // version needed to extract
+        out.extend_from_slice(&0u16.to_le_bytes()); // flags
+        out.extend_from_slice(&method.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // mod time
+        out.extend_from_slice(&0u16.to_le_bytes()); // mod date
+        out.extend_from_slice(&crc.to_le_bytes());
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(name_bytes.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        out.extend_from_slice(name_bytes);
+        out.extend_from_slice(&payload);
+
+        central_dir.push(CentralDirEntry {
+            method,
+            crc32: crc,
+            compressed_size: payload.len() as u32,
+            uncompressed_size: data.len() as u32,
+            name: path,
+            local_header_offset,
+        });
+    }
+
+    let central_dir_offset = out.len() as u32;
+    for entry in &central_dir {
+        let name_bytes = entry.name.as_bytes();
+        out.extend_from_slice(&CENTRAL_DIR_HEADER_SIG.to_le_bytes());
+        out.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        out.extend_from_slice(&20u16.to_le_bytes()); // version needed to extract
+        out.extend_from_slice(&0u16.to_le_bytes()); // flags
+        out.extend_from_slice(&entry.method.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // mod time
+        out.extend_from_slice(&0u16.to_le_bytes()); // mod date
+        out.extend_from_slice(&entry.crc32.to_le_bytes());
+        out.extend_from_slice(&entry.compressed_size.to_le_bytes());
+        out.extend_from_slice(&entry.uncompressed_size.to_le_bytes());
+        out.extend_from_slice(&(name_bytes.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+        out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        out.extend_from_slice(&0u16.to_le_bytes()); // disk number start
+        out.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        out.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        out.extend_from_slice(&entry.local_header_offset.to_le_bytes());
+        out.extend_from_slice(name_bytes);
+    }
+    let central_dir_size = out.len() as u32 - central_dir_offset;
+
+    out.extend_from_slice(&END_OF_CENTRAL_DIR_SIG.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // disk number
+    out.extend_from_slice(&0u16.to_le_bytes()); // disk with central dir
+    out.extend_from_slice(&(central_dir.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central_dir.len() as u16).to_le_bytes());
+    out.extend_from_slice(&central_dir_size.to_le_bytes());
+    out.extend_from_slice(&central_dir_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+    out
+}
+
+const CRC32_POLYNOMIAL: u32 = 0xEDB8_8320;
+
+fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Standard CRC-32 (polynomial `0xEDB88320`), as required in every ZIP entry.
+pub fn crc32(data: &[u8]) -> u32 {
+    let table = crc32_table();
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        let index = ((crc ^ byte as u32) & 0xFF) as usize;
+        crc = (crc >> 8) ^ table[index];
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeVfs {
+        files: Vec<(&'static str, &'static [u8])>,
+    }
+
+    impl Vfs for FakeVfs {
+        fn list(&self) -> Vec<String> {
+            self.files.iter().map(|(path, _)| path.to_string()).collect()
+        }
+
+        fn read(&self, path: &str) -> Option<Vec<u8>> {
+            self.files
+                .iter()
+                .find(|(p, _)| *p == path)
+                .map(|(_, data)| data.to_vec())
+        }
+
+        fn write(&mut self, _path: &str, _data: Vec<u8>) {
+            unimplemented!("build_zip only reads from the Vfs")
+        }
+    }
+
+    #[test]
+    fn crc32_matches_the_reference_check_value() {
+        // The canonical CRC-32 check value from RFC 1952.
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    /// Parses a single local file header out of `zip` at `offset` and
+    /// returns `(method, crc32, compressed, uncompressed_size, name, payload)`.
+    fn read_local_header(zip: &[u8], offset: usize) -> (u16, u32, u32, u32, String, Vec<u8>) {
+        assert_eq!(&zip[offset..offset + 4], &LOCAL_FILE_HEADER_SIG.to_le_bytes());
+        let method = u16::from_le_bytes(zip[offset + 8..offset + 10].try_into().unwrap());
+        let crc = u32::from_le_bytes(zip[offset + 14..offset + 18].try_into().unwrap());
+        let compressed_size = u32::from_le_bytes(zip[offset + 18..offset + 22].try_into().unwrap());
+        let uncompressed_size = u32::from_le_bytes(zip[offset + 22..offset + 26].try_into().unwrap());
+        let name_len = u16::from_le_bytes(zip[offset + 26..offset + 28].try_into().unwrap()) as usize;
+        let name_start = offset + 30;
+        let name = String::from_utf8(zip[name_start..name_start + name_len].to_vec()).unwrap();
+        let data_start = name_start + name_len;
+        let payload = zip[data_start..data_start + compressed_size as usize].to_vec();
+        (method, crc, compressed_size, uncompressed_size, name, payload)
+    }
+
+    #[test]
+    fn stored_zip_round_trips_without_compression() {
+        let vfs = FakeVfs {
+            files: vec![("Cargo.toml", b"[package]\n"), ("src/lib.rs", b"fn main() {}")],
+        };
+
+        let zip = build_zip(&vfs, ZipCompression::Store);
+        let (method, crc, compressed_size, uncompressed_size, name, payload) = read_local_header(&zip, 0);
+
+        assert_eq!(method, METHOD_STORE);
+        assert_eq!(name, "Cargo.toml");
+        assert_eq!(compressed_size, uncompressed_size);
+        assert_eq!(crc, crc32(b"[package]\n"));
+        assert_eq!(payload, b"[package]\n");
+
+        assert!(zip.windows(4).any(|w| w == CENTRAL_DIR_HEADER_SIG.to_le_bytes()));
+        assert!(zip.windows(4).any(|w| w == END_OF_CENTRAL_DIR_SIG.to_le_bytes()));
+    }
+
+    #[test]
+    fn deflate_zip_round_trips_through_miniz_oxide() {
+        let data = b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        let vfs = FakeVfs {
+            files: vec![("README.md", data)],
+        };
+
+        let zip = build_zip(&vfs, ZipCompression::Deflate);
+        let (method, crc, compressed_size, uncompressed_size, name, payload) = read_local_header(&zip, 0);
+
+        assert_eq!(method, METHOD_DEFLATE);
+        assert_eq!(name, "README.md");
+        assert_eq!(uncompressed_size, data.len() as u32);
+        assert!((compressed_size as usize) < data.len());
+        assert_eq!(crc, crc32(data));
+
+        let decompressed = miniz_oxide::inflate::decompress_to_vec(&payload).expect("valid deflate stream");
+        assert_eq!(decompressed, data);
+    }
+}